@@ -4,7 +4,6 @@
 #![no_std]
 
 extern crate alloc;
-extern crate linked_list_allocator;
 extern crate memadvise;
 extern crate spin;
 
@@ -14,7 +13,6 @@ use core::ops::Deref;
 use alloc::alloc::{Alloc, AllocErr, Layout};
 use core::alloc::GlobalAlloc;
 use core::ptr::NonNull;
-use slab::Slab;
 use spin::Mutex;
 
 // used for testing only
@@ -25,14 +23,288 @@ pub const NUM_OF_SLABS: usize = 8;
 pub const MIN_SLAB_SIZE: usize = 4096;
 pub const MIN_HEAP_SIZE: usize = NUM_OF_SLABS * MIN_SLAB_SIZE;
 
+// Upper bound on the number of blocks a single slab (including anything added later via
+// `grow`) can track when the `slab-bitmap` feature is enabled. 8192 blocks is enough headroom
+// for the 64-byte class of a heap several times the minimum size; deployments that need more
+// should simply leave the feature disabled and keep the lean free-list.
+#[cfg(feature = "slab-bitmap")]
+pub const MAX_SLAB_BLOCKS: usize = 8192;
+#[cfg(feature = "slab-bitmap")]
+pub const SLAB_BITMAP_WORDS: usize = MAX_SLAB_BLOCKS / 32;
+
+// Intrusive free-list node written into the first bytes of a free block. Unused when the
+// `slab-bitmap` feature is enabled, since that mode tracks occupancy out-of-line instead.
+struct SlabEntry {
+    m_next: Option<NonNull<SlabEntry>>,
+}
+
 pub struct Slab {
-    m_nextSlab: Slab,
-    m_freeList: SlabEntry,
+    m_nextSlab: Option<NonNull<Slab>>,
+    m_freeList: Option<NonNull<SlabEntry>>,
     m_slabStart: u32,
+    // Exclusive end of the highest region handed to this slab so far via `new`/`grow`, used to
+    // tell whether a pointer genuinely belongs to this slab (as opposed to one borrowed from it
+    // during cross-class fallback, which carries its own header instead).
+    m_regionEnd: u32,
     m_size: u16,
+    m_blockCount: u32,
+    #[cfg(feature = "slab-bitmap")]
+    m_occupancy: [u32; SLAB_BITMAP_WORDS],
+    // Bit `i` marks the `i`th page-aligned, `MIN_SLAB_SIZE`-sized page of this slab's region as
+    // handed back to the OS via `memadvise::advise`. Bounded to 64 pages per slab so the flag
+    // can live inline instead of needing its own dynamically-sized allocation; slabs larger than
+    // that simply never get their tail pages decommitted.
+    m_decommitted: u64,
 }
 
-#[derive(Copy, Clone)]
+impl Slab {
+    // Creates a new slab of fixed-size `block_size` blocks spanning `[slab_start, slab_start +
+    // slab_size)`. This function is unsafe because it can cause undefined behavior if the given
+    // region is invalid or already in use for anything else.
+    pub unsafe fn new(slab_start: usize, slab_size: usize, block_size: u16) -> Slab {
+        let mut slab = Slab {
+            m_nextSlab: None,
+            m_freeList: None,
+            m_slabStart: slab_start as u32,
+            m_regionEnd: slab_start as u32,
+            m_size: block_size,
+            m_blockCount: 0,
+            #[cfg(feature = "slab-bitmap")]
+            m_occupancy: [0; SLAB_BITMAP_WORDS],
+            m_decommitted: 0,
+        };
+        slab.grow(slab_start, slab_size);
+        slab
+    }
+
+    // Adds `[mem_start_addr, mem_start_addr + mem_size)` to this slab. This function is unsafe
+    // because it can cause undefined behavior if the given region is invalid.
+    #[cfg(not(feature = "slab-bitmap"))]
+    pub unsafe fn grow(&mut self, mem_start_addr: usize, mem_size: usize) {
+        let block_size = self.m_size as usize;
+        let num_of_blocks = mem_size / block_size;
+        for i in 0..num_of_blocks {
+            self.push_free_block(mem_start_addr + i * block_size);
+        }
+        self.m_blockCount += num_of_blocks as u32;
+        self.m_regionEnd = self.m_regionEnd.max((mem_start_addr + mem_size) as u32);
+    }
+
+    // Bitmap mode needs no free-list bookkeeping: a block is free whenever its bit is clear, and
+    // the array starts zeroed, so growing the slab is just widening the tracked block count.
+    #[cfg(feature = "slab-bitmap")]
+    pub unsafe fn grow(&mut self, mem_start_addr: usize, mem_size: usize) {
+        let num_of_blocks = mem_size / self.m_size as usize;
+        assert!(
+            self.m_blockCount as usize + num_of_blocks <= MAX_SLAB_BLOCKS,
+            "slab grew past MAX_SLAB_BLOCKS with the slab-bitmap feature enabled"
+        );
+        self.m_blockCount += num_of_blocks as u32;
+        self.m_regionEnd = self.m_regionEnd.max((mem_start_addr + mem_size) as u32);
+    }
+
+    // Whether `addr` physically lives inside this slab's backing region, as opposed to being a
+    // pointer this slab never owned (e.g. one carved from a different class during fallback).
+    pub fn contains(&self, addr: usize) -> bool {
+        addr >= self.m_slabStart as usize && addr < self.m_regionEnd as usize
+    }
+
+    #[cfg(not(feature = "slab-bitmap"))]
+    unsafe fn push_free_block(&mut self, addr: usize) {
+        let node = addr as *mut SlabEntry;
+        (*node).m_next = self.m_freeList;
+        self.m_freeList = NonNull::new(node);
+    }
+
+    #[cfg(feature = "slab-bitmap")]
+    fn block_index(&self, addr: usize) -> usize {
+        (addr - self.m_slabStart as usize) / self.m_size as usize
+    }
+
+    // Pops a free block, or returns `AllocErr` if the slab is exhausted.
+    #[cfg(not(feature = "slab-bitmap"))]
+    pub unsafe fn allocate(&mut self, _layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        match self.m_freeList {
+            Some(node) => {
+                self.m_freeList = node.as_ref().m_next;
+                let addr = node.as_ptr() as usize;
+                self.zero_if_decommitted(addr);
+                Ok(NonNull::new_unchecked(addr as *mut u8))
+            }
+            None => Err(AllocErr),
+        }
+    }
+
+    // Scans the occupancy bitmap for a clear bit, using `trailing_zeros` on the inverted word
+    // as a fast path to the lowest free block in that word, sets the bit, and returns the
+    // corresponding address. Returns `AllocErr` if every tracked block is allocated.
+    #[cfg(feature = "slab-bitmap")]
+    pub unsafe fn allocate(&mut self, _layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        for (word_idx, word) in self.m_occupancy.iter_mut().enumerate() {
+            if *word == u32::max_value() {
+                continue;
+            }
+            let bit = (!*word).trailing_zeros() as usize;
+            let index = word_idx * 32 + bit;
+            if index >= self.m_blockCount as usize {
+                break;
+            }
+            *word |= 1 << bit;
+            let addr = self.m_slabStart as usize + index * self.m_size as usize;
+            self.zero_if_decommitted(addr);
+            return Ok(NonNull::new_unchecked(addr as *mut u8));
+        }
+        Err(AllocErr)
+    }
+
+    // Returns `ptr` to this slab's free list. `ptr` must have been returned by a previous call
+    // to `allocate` on this slab; undefined behavior may occur otherwise, thus this function is
+    // unsafe.
+    #[cfg(not(feature = "slab-bitmap"))]
+    pub unsafe fn deallocate(&mut self, ptr: NonNull<u8>) {
+        self.push_free_block(ptr.as_ptr() as usize);
+    }
+
+    // Clears `ptr`'s occupancy bit so the block can be reused. A clear bit here means `ptr` was
+    // already free or never came from this slab, so this asserts instead of silently corrupting
+    // an allocator that a free-list design couldn't have caught either. `contains`/the block
+    // count are checked before `block_index`'s subtraction and before indexing `m_occupancy`, so
+    // a genuinely foreign pointer hits this diagnostic instead of underflowing into a garbage
+    // index and panicking with a generic out-of-bounds message.
+    #[cfg(feature = "slab-bitmap")]
+    pub unsafe fn deallocate(&mut self, ptr: NonNull<u8>) {
+        let addr = ptr.as_ptr() as usize;
+        assert!(
+            self.contains(addr),
+            "double free or foreign pointer: address does not belong to this slab"
+        );
+
+        let index = self.block_index(addr);
+        assert!(
+            index < self.m_blockCount as usize,
+            "double free or foreign pointer: block {} is out of range",
+            index
+        );
+
+        let word = index / 32;
+        let bit = index % 32;
+        assert!(
+            self.m_occupancy[word] & (1 << bit) != 0,
+            "double free or foreign pointer: block {} was not marked allocated",
+            index
+        );
+        self.m_occupancy[word] &= !(1 << bit);
+    }
+
+    #[cfg(feature = "slab-bitmap")]
+    fn is_block_free(&self, index: usize) -> bool {
+        self.m_occupancy[index / 32] & (1 << (index % 32)) == 0
+    }
+
+    // If `addr`'s page was previously handed back to the OS by `advise_free`, zero it before
+    // handing it out again (the OS may not zero a re-faulted page on every platform) and clear
+    // the decommitted flag for that page.
+    unsafe fn zero_if_decommitted(&mut self, addr: usize) {
+        let page_index = (addr - self.m_slabStart as usize) / MIN_SLAB_SIZE;
+        if page_index >= 64 {
+            return;
+        }
+        let page_bit = 1u64 << page_index;
+        if self.m_decommitted & page_bit != 0 {
+            let page_addr = self.m_slabStart as usize + page_index * MIN_SLAB_SIZE;
+            core::ptr::write_bytes(page_addr as *mut u8, 0, MIN_SLAB_SIZE);
+            self.m_decommitted &= !page_bit;
+        }
+    }
+
+    // Walks this slab's region one `MIN_SLAB_SIZE` page at a time and hands any page whose
+    // blocks are all currently free back to the OS with `memadvise::advise`, so a slab that
+    // ballooned during a burst of allocations doesn't permanently pin `slab_size` of physical
+    // memory. Pages are re-faulted and zeroed lazily by `allocate` the next time a block from
+    // them is handed out.
+    //
+    // Requires the `slab-bitmap` feature: occupancy there lives out-of-line in `m_occupancy`, so
+    // a decommitted page holds nothing `allocate`/`deallocate` need to read. The non-bitmap free
+    // list is intrusive — every free block's `SlabEntry.m_next` lives inside the free memory
+    // itself — so `memadvise::advise(..., DontNeed)` would zero those links out from under the
+    // list the instant their page was decommitted; see the `not(slab-bitmap)` overload below.
+    #[cfg(feature = "slab-bitmap")]
+    pub fn advise_free(&mut self) {
+        let block_size = self.m_size as usize;
+        let blocks_per_page = MIN_SLAB_SIZE / block_size;
+        let num_pages = (self.m_blockCount as usize * block_size) / MIN_SLAB_SIZE;
+
+        for page_index in 0..num_pages.min(64) {
+            let page_bit = 1u64 << page_index;
+            if self.m_decommitted & page_bit != 0 {
+                continue;
+            }
+
+            let first_block = page_index * blocks_per_page;
+            let page_is_free =
+                (first_block..first_block + blocks_per_page).all(|i| self.is_block_free(i));
+
+            if page_is_free {
+                let page_addr = self.m_slabStart as usize + page_index * MIN_SLAB_SIZE;
+                let _ = memadvise::advise(page_addr, MIN_SLAB_SIZE, memadvise::Advice::DontNeed);
+                self.m_decommitted |= page_bit;
+            }
+        }
+    }
+
+    // No-op without `slab-bitmap`: this slab's free blocks track their own occupancy via
+    // `SlabEntry.m_next` pointers stored inside the free memory itself, so decommitting a page
+    // of them with `memadvise::advise(..., DontNeed)` would zero those links in place and
+    // silently truncate the free list, leaking every block reachable past the decommitted page.
+    // Enable `slab-bitmap` to get real page decommit out of `advise_free`.
+    #[cfg(not(feature = "slab-bitmap"))]
+    pub fn advise_free(&mut self) {}
+
+    // Returns the occupancy of this slab: how many blocks it has room for in total, how many of
+    // those are currently free, and how many bytes that implies are in use.
+    pub fn stats(&self) -> SlabStats {
+        let block_size = self.m_size as usize;
+        let total_blocks = self.m_blockCount as usize;
+        let free_blocks = self.free_block_count();
+        SlabStats {
+            block_size,
+            total_blocks,
+            free_blocks,
+            bytes_in_use: (total_blocks - free_blocks) * block_size,
+        }
+    }
+
+    #[cfg(feature = "slab-bitmap")]
+    fn free_block_count(&self) -> usize {
+        let total_blocks = self.m_blockCount as usize;
+        let allocated_blocks: usize =
+            self.m_occupancy.iter().map(|word| word.count_ones() as usize).sum();
+        total_blocks - allocated_blocks
+    }
+
+    #[cfg(not(feature = "slab-bitmap"))]
+    fn free_block_count(&self) -> usize {
+        let mut count = 0;
+        let mut current = self.m_freeList;
+        while let Some(node) = current {
+            count += 1;
+            current = unsafe { node.as_ref().m_next };
+        }
+        count
+    }
+}
+
+// Occupancy snapshot for a single fixed-size slab class, as returned by `Slab::stats` and
+// aggregated into `HeapStats`.
+#[derive(Copy, Clone, Debug)]
+pub struct SlabStats {
+    pub block_size: usize,
+    pub total_blocks: usize,
+    pub free_blocks: usize,
+    pub bytes_in_use: usize,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub enum HeapAllocator {
     Slab64Bytes,
     Slab128Bytes,
@@ -41,7 +313,201 @@ pub enum HeapAllocator {
     Slab1024Bytes,
     Slab2048Bytes,
     Slab4096Bytes,
-    LinkedListAllocator,
+    BuddySystemAllocator,
+}
+
+// Fallback order used by `Heap::allocate`: when the class `layout_to_allocator` would pick is
+// exhausted, the next entry in this slice is tried instead, up to and including the buddy
+// allocator, rather than failing the allocation outright.
+const CLASS_FALLBACK_ORDER: [HeapAllocator; 8] = [
+    HeapAllocator::Slab64Bytes,
+    HeapAllocator::Slab128Bytes,
+    HeapAllocator::Slab256Bytes,
+    HeapAllocator::Slab512Bytes,
+    HeapAllocator::Slab1024Bytes,
+    HeapAllocator::Slab2048Bytes,
+    HeapAllocator::Slab4096Bytes,
+    HeapAllocator::BuddySystemAllocator,
+];
+
+// Blocks borrowed from a class other than the one `layout_to_allocator` would pick (i.e. actual
+// cross-class fallback) are prefixed with this many bytes recording the class they were really
+// carved from (see `Heap::allocate`/`Heap::deallocate`). The common, non-fallback path never
+// pays for or loses alignment to this header — it is only written, and the returned pointer only
+// offset past it, when a block is actually borrowed from a larger class. `layout_to_allocator`'s
+// per-class ceilings are kept `FALLBACK_HEADER_SIZE` below each class's raw block size so that a
+// request routed to a class as a fallback target always has room for the header alongside the
+// original request.
+const FALLBACK_HEADER_SIZE: usize = 8;
+
+// Number of orders the buddy subsystem tracks (orders `0` through `MAX_BUDDY_ORDER - 1`), i.e.
+// the biggest block it can ever hand out is `MIN_SLAB_SIZE << (MAX_BUDDY_ORDER - 1)`. 32 orders
+// comfortably covers any region size the 8-way heap split in `Heap::new` can produce.
+pub const MAX_BUDDY_ORDER: usize = 32;
+
+// Intrusive free-list node written into the first bytes of a free block. No side allocation is
+// needed to track free blocks, since the memory is unused while it sits on a free list.
+struct BuddyFreeBlock {
+    m_next: Option<NonNull<BuddyFreeBlock>>,
+}
+
+// A coalescing buddy allocator for the region that used to be handed entirely to
+// `linked_list_allocator`. Blocks are powers of two of `MIN_SLAB_SIZE`; an order-`k` block has
+// size `MIN_SLAB_SIZE << k`. Splitting a block produces two order-(k-1) "buddies" whose
+// addresses differ in exactly one bit, so a freed block's buddy can be located with a single
+// XOR and merged back together, which keeps large allocations from fragmenting the region the
+// way the old linked-list path could.
+pub struct BuddyAllocator {
+    m_regionStart: usize,
+    m_regionSize: usize,
+    m_maxOrder: usize,
+    m_freeLists: [Option<NonNull<BuddyFreeBlock>>; MAX_BUDDY_ORDER],
+}
+
+impl BuddyAllocator {
+    // Creates a new buddy allocator managing `[region_start, region_start + region_size)`.
+    // Buddy addresses are derived by XOR-ing a block's offset from `region_start`, so
+    // `region_start` need not be aligned to `region_size`. `region_size` need not itself be a power-of-two multiple of
+    // `MIN_SLAB_SIZE`: since only one top-level block is ever tracked, it is rounded down to the
+    // largest one that fits, and that rounded size — not the raw argument — is what `m_regionSize`
+    // stores and every other method (`contains`, `stats`, ...) sees, so a non-power-of-two region
+    // never causes a silent, invisible loss of trailing memory. This function is unsafe because
+    // it can cause undefined behavior if the given region overlaps memory used for anything else.
+    pub unsafe fn new(region_start: usize, region_size: usize) -> BuddyAllocator {
+        assert!(
+            region_size >= MIN_SLAB_SIZE,
+            "Buddy region should be at least MIN_SLAB_SIZE"
+        );
+
+        let mut max_order = 0;
+        while (MIN_SLAB_SIZE << (max_order + 1)) <= region_size {
+            max_order += 1;
+        }
+        let tracked_region_size = MIN_SLAB_SIZE << max_order;
+
+        let mut allocator = BuddyAllocator {
+            m_regionStart: region_start,
+            m_regionSize: tracked_region_size,
+            m_maxOrder: max_order,
+            m_freeLists: [None; MAX_BUDDY_ORDER],
+        };
+        allocator.push_free_block(region_start, max_order);
+        allocator
+    }
+
+    // Smallest order `k` such that `MIN_SLAB_SIZE << k` is at least `size`.
+    fn order_for_size(&self, size: usize) -> usize {
+        let mut order = 0;
+        while (MIN_SLAB_SIZE << order) < size {
+            order += 1;
+        }
+        order
+    }
+
+    unsafe fn push_free_block(&mut self, addr: usize, order: usize) {
+        let node = addr as *mut BuddyFreeBlock;
+        (*node).m_next = self.m_freeLists[order];
+        self.m_freeLists[order] = NonNull::new(node);
+    }
+
+    // Removes the free block at `addr` from the order-`order` free list, if present.
+    unsafe fn remove_free_block(&mut self, addr: usize, order: usize) -> bool {
+        let mut current = self.m_freeLists[order];
+        let mut prev: Option<NonNull<BuddyFreeBlock>> = None;
+        while let Some(node) = current {
+            if node.as_ptr() as usize == addr {
+                match prev {
+                    Some(mut p) => p.as_mut().m_next = node.as_ref().m_next,
+                    None => self.m_freeLists[order] = node.as_ref().m_next,
+                }
+                return true;
+            }
+            prev = current;
+            current = node.as_ref().m_next;
+        }
+        false
+    }
+
+    // Pops a free block of exactly `order`, splitting the smallest larger free block (and
+    // pushing its unused buddy halves onto their own free lists) if none is available directly.
+    unsafe fn allocate_order(&mut self, order: usize) -> Option<usize> {
+        for candidate in order..=self.m_maxOrder {
+            if let Some(node) = self.m_freeLists[candidate] {
+                self.m_freeLists[candidate] = node.as_ref().m_next;
+                let mut addr = node.as_ptr() as usize;
+                for split_order in (order..candidate).rev() {
+                    let buddy = addr + (MIN_SLAB_SIZE << split_order);
+                    self.push_free_block(buddy, split_order);
+                }
+                let _ = addr;
+                return Some(node.as_ptr() as usize);
+            }
+        }
+        None
+    }
+
+    pub unsafe fn allocate(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        let order = self.order_for_size(layout.size().max(layout.align()));
+        if order > self.m_maxOrder {
+            return Err(AllocErr);
+        }
+        match self.allocate_order(order) {
+            Some(addr) => Ok(NonNull::new_unchecked(addr as *mut u8)),
+            None => Err(AllocErr),
+        }
+    }
+
+    // Frees the block at `ptr`, repeatedly merging it with its buddy for as long as the buddy
+    // is also free. The buddy of a block is found by flipping the bit corresponding to the
+    // block's size in its offset from the start of the region.
+    pub unsafe fn deallocate(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        let mut order = self.order_for_size(layout.size().max(layout.align()));
+        let mut addr = ptr.as_ptr() as usize;
+
+        while order < self.m_maxOrder {
+            let block_size = MIN_SLAB_SIZE << order;
+            let offset = addr - self.m_regionStart;
+            let buddy_addr = self.m_regionStart + (offset ^ block_size);
+
+            if self.remove_free_block(buddy_addr, order) {
+                addr = if buddy_addr < addr { buddy_addr } else { addr };
+                order += 1;
+            } else {
+                break;
+            }
+        }
+
+        self.push_free_block(addr, order);
+    }
+
+    pub fn usable_size(&self, layout: &Layout) -> (usize, usize) {
+        let order = self.order_for_size(layout.size().max(layout.align()));
+        (layout.size(), MIN_SLAB_SIZE << order)
+    }
+
+    // Whether `addr` physically lives inside this region, as opposed to being a pointer this
+    // allocator never owned (e.g. one carved from a slab class during fallback).
+    pub fn contains(&self, addr: usize) -> bool {
+        addr >= self.m_regionStart && addr < self.m_regionStart + self.m_regionSize
+    }
+
+    // Returns `(total_bytes, free_bytes)` for this region, found by summing the block size of
+    // every free list across every order. `total_bytes` is `m_regionSize`, which `new` already
+    // rounds down to the single top-level block actually tracked, so an idle allocator always
+    // reports `free_bytes == total_bytes` instead of disagreeing by whatever tail a non-power-
+    // of-two `region_size` would otherwise have silently dropped.
+    pub fn stats(&self) -> (usize, usize) {
+        let mut free_bytes = 0;
+        for order in 0..=self.m_maxOrder {
+            let block_size = MIN_SLAB_SIZE << order;
+            let mut current = self.m_freeLists[order];
+            while let Some(node) = current {
+                free_bytes += block_size;
+                current = unsafe { node.as_ref().m_next };
+            }
+        }
+        (self.m_regionSize, free_bytes)
+    }
 }
 
 pub struct Heap {
@@ -52,7 +518,7 @@ pub struct Heap {
     slab_1024_bytes: Slab,
     slab_2048_bytes: Slab,
     slab_4096_bytes: Slab,
-    linked_list_allocator: linked_list_allocator::Heap,
+    buddy_allocator: BuddyAllocator,
 }
 
 impl Heap {
@@ -85,10 +551,94 @@ impl Heap {
             slab_1024_bytes: Slab::new(heap_start_addr + 4 * slab_size, slab_size, 1024),
             slab_2048_bytes: Slab::new(heap_start_addr + 5 * slab_size, slab_size, 2048),
             slab_4096_bytes: Slab::new(heap_start_addr + 6 * slab_size, slab_size, 4096),
-            linked_list_allocator: linked_list_allocator::Heap::new(
-                heap_start_addr + 7 * slab_size,
-                slab_size,
-            ),
+            buddy_allocator: BuddyAllocator::new(heap_start_addr + 7 * slab_size, slab_size),
+        }
+    }
+
+    // Allocates a block of memory described by `layout`. This function finds the slab of the
+    // smallest size class that fits the request; if that class is exhausted, it walks up
+    // `CLASS_FALLBACK_ORDER` and carves a block from the next non-empty class (or the buddy
+    // allocator) instead of failing outright, since an empty 64-byte slab shouldn't turn away
+    // an allocation while the 2048- and 4096-byte slabs sit idle. A block carved from a class
+    // other than the preferred one is tagged with its true owning class so `deallocate` can
+    // return it correctly even though the class a fallback allocation is freed under no longer
+    // matches the class it lives in; a block served by the preferred class is untagged. This
+    // function is unsafe because the returned pointer must be freed with `deallocate` using the
+    // identical layout.
+    pub unsafe fn allocate(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        let preferred = Heap::layout_to_allocator(&layout);
+        let start = CLASS_FALLBACK_ORDER
+            .iter()
+            .position(|class| *class == preferred)
+            .unwrap();
+
+        for &class in &CLASS_FALLBACK_ORDER[start..] {
+            if let Ok(ptr) = self.allocate_from_class(class, preferred, layout) {
+                return Ok(ptr);
+            }
+        }
+        Err(AllocErr)
+    }
+
+    // Carves a block from `class`. When `class` is the one `layout` would naturally map to, the
+    // raw block is returned as-is: no header, no offset, full capacity and alignment. Only when
+    // `class` differs from `preferred` (an actual cross-class fallback) is the
+    // `FALLBACK_HEADER_SIZE`-byte owner tag written and the returned pointer offset past it, so
+    // `deallocate` can later tell the two cases apart by checking which slab's region the
+    // pointer falls in.
+    unsafe fn allocate_from_class(
+        &mut self,
+        class: HeapAllocator,
+        preferred: HeapAllocator,
+        layout: Layout,
+    ) -> Result<NonNull<u8>, AllocErr> {
+        let is_fallback = class != preferred;
+        let request_layout = if is_fallback && class == HeapAllocator::BuddySystemAllocator {
+            Heap::padded_layout(&layout)?
+        } else {
+            layout
+        };
+
+        let block = match class {
+            HeapAllocator::Slab64Bytes => self.slab_64_bytes.allocate(request_layout),
+            HeapAllocator::Slab128Bytes => self.slab_128_bytes.allocate(request_layout),
+            HeapAllocator::Slab256Bytes => self.slab_256_bytes.allocate(request_layout),
+            HeapAllocator::Slab512Bytes => self.slab_512_bytes.allocate(request_layout),
+            HeapAllocator::Slab1024Bytes => self.slab_1024_bytes.allocate(request_layout),
+            HeapAllocator::Slab2048Bytes => self.slab_2048_bytes.allocate(request_layout),
+            HeapAllocator::Slab4096Bytes => self.slab_4096_bytes.allocate(request_layout),
+            HeapAllocator::BuddySystemAllocator => self.buddy_allocator.allocate(request_layout),
+        }?;
+
+        if !is_fallback {
+            return Ok(block);
+        }
+
+        let block_addr = block.as_ptr() as usize;
+        (block_addr as *mut HeapAllocator).write(class);
+        Ok(NonNull::new_unchecked((block_addr + FALLBACK_HEADER_SIZE) as *mut u8))
+    }
+
+    // Grows `layout` by `FALLBACK_HEADER_SIZE` bytes for allocations that end up carved from the
+    // buddy allocator as a fallback target, which (unlike the slabs) sizes its blocks from the
+    // layout it is given.
+    fn padded_layout(layout: &Layout) -> Result<Layout, AllocErr> {
+        Layout::from_size_align(layout.size() + FALLBACK_HEADER_SIZE, layout.align())
+            .map_err(|_| AllocErr)
+    }
+
+    // True if `ptr` lives inside the region backing `class`, i.e. `class` served `ptr` directly
+    // rather than `ptr` being the offset-past-header result of a fallback allocation.
+    fn class_contains(&self, class: HeapAllocator, addr: usize) -> bool {
+        match class {
+            HeapAllocator::Slab64Bytes => self.slab_64_bytes.contains(addr),
+            HeapAllocator::Slab128Bytes => self.slab_128_bytes.contains(addr),
+            HeapAllocator::Slab256Bytes => self.slab_256_bytes.contains(addr),
+            HeapAllocator::Slab512Bytes => self.slab_512_bytes.contains(addr),
+            HeapAllocator::Slab1024Bytes => self.slab_1024_bytes.contains(addr),
+            HeapAllocator::Slab2048Bytes => self.slab_2048_bytes.contains(addr),
+            HeapAllocator::Slab4096Bytes => self.slab_4096_bytes.contains(addr),
+            HeapAllocator::BuddySystemAllocator => self.buddy_allocator.contains(addr),
         }
     }
 
@@ -105,7 +655,9 @@ impl Heap {
             HeapAllocator::Slab1024Bytes => self.slab_1024_bytes.grow(mem_start_addr, mem_size),
             HeapAllocator::Slab2048Bytes => self.slab_2048_bytes.grow(mem_start_addr, mem_size),
             HeapAllocator::Slab4096Bytes => self.slab_4096_bytes.grow(mem_start_addr, mem_size),
-            HeapAllocator::LinkedListAllocator => self.linked_list_allocator.extend(mem_size),
+            HeapAllocator::BuddySystemAllocator => {
+                panic!("growing the buddy allocator region is not supported")
+            }
         }
     }
 
@@ -113,11 +665,42 @@ impl Heap {
     // by a call to the `allocate` function with identical size and alignment. Undefined
     // behavior may occur for invalid arguments, thus this function is unsafe.
     //
-    // This function finds the slab which contains address of `ptr` and adds the blocks beginning
-    // with `ptr` address to the list of free blocks.
-    // This operation is in `O(1)` for blocks <= 4096 bytes and `O(n)` for blocks > 4096 bytes.
+    // `layout` alone can't tell a direct allocation from a fallback one, so this first checks
+    // whether `ptr` actually lives inside the class `layout_to_allocator` would pick: if so, it
+    // was served directly (no header) and is freed as-is. Otherwise `ptr` must be the
+    // offset-past-header result of a cross-class fallback, so the owner tag written just before
+    // it is read to find where it really lives.
+    // This operation is in `O(1)` for blocks served by a slab and takes `O(log n)` merge steps
+    // for blocks served by the buddy allocator (requests over `4096 - FALLBACK_HEADER_SIZE`
+    // bytes, or fallbacks from the 4096-byte slab), where `n` is the size of the buddy region.
     pub unsafe fn deallocate(&mut self, ptr: NonNull<u8>, layout: Layout) {
-        match Heap::layout_to_allocator(&layout) {
+        let preferred = Heap::layout_to_allocator(&layout);
+        let addr = ptr.as_ptr() as usize;
+
+        if self.class_contains(preferred, addr) {
+            self.deallocate_to_class(preferred, ptr, layout, false);
+            return;
+        }
+
+        let block_addr = addr - FALLBACK_HEADER_SIZE;
+        let owner = (block_addr as *const HeapAllocator).read();
+        let block = NonNull::new_unchecked(block_addr as *mut u8);
+        self.deallocate_to_class(owner, block, layout, true);
+    }
+
+    // Frees `ptr` to `class`, treating `ptr` as the true, unoffset start of its block. `layout`
+    // is only consulted for the buddy allocator, which (unlike the slabs) needs it to recompute
+    // the block's order; `was_fallback` says whether `allocate` padded that layout by
+    // `FALLBACK_HEADER_SIZE` when it carved this block out as a fallback target, so the same
+    // padding must be reapplied here to land on the same order.
+    unsafe fn deallocate_to_class(
+        &mut self,
+        class: HeapAllocator,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        was_fallback: bool,
+    ) {
+        match class {
             HeapAllocator::Slab64Bytes => self.slab_64_bytes.deallocate(ptr),
             HeapAllocator::Slab128Bytes => self.slab_128_bytes.deallocate(ptr),
             HeapAllocator::Slab256Bytes => self.slab_256_bytes.deallocate(ptr),
@@ -125,47 +708,169 @@ impl Heap {
             HeapAllocator::Slab1024Bytes => self.slab_1024_bytes.deallocate(ptr),
             HeapAllocator::Slab2048Bytes => self.slab_2048_bytes.deallocate(ptr),
             HeapAllocator::Slab4096Bytes => self.slab_4096_bytes.deallocate(ptr),
-            HeapAllocator::LinkedListAllocator => {
-                self.linked_list_allocator.deallocate(ptr, layout)
+            HeapAllocator::BuddySystemAllocator => {
+                let request_layout = if was_fallback {
+                    Heap::padded_layout(&layout).expect("layout overflowed on padding")
+                } else {
+                    layout
+                };
+                self.buddy_allocator.deallocate(ptr, request_layout)
             }
         }
     }
 
-    // Returns bounds on the guaranteed usable size of a successful
-    // allocation created with the specified `layout`.
+    // Walks every fixed-size slab and hands any page-aligned run of fully-free blocks back to
+    // the OS via `memadvise`, so a slab that ballooned during a burst of small allocations
+    // doesn't keep pinning `slab_size` of physical memory once the burst is over. The large-
+    // object region is left alone, since the buddy allocator already merges free blocks instead
+    // of pinning them to a class. Without the `slab-bitmap` feature every slab's `advise_free`
+    // is a no-op, so this decommits nothing.
+    pub fn advise_free(&mut self) {
+        self.slab_64_bytes.advise_free();
+        self.slab_128_bytes.advise_free();
+        self.slab_256_bytes.advise_free();
+        self.slab_512_bytes.advise_free();
+        self.slab_1024_bytes.advise_free();
+        self.slab_2048_bytes.advise_free();
+        self.slab_4096_bytes.advise_free();
+    }
+
+    // Returns bounds on the guaranteed usable size of a successful allocation created with the
+    // specified `layout`, assuming it lands in the class `layout_to_allocator` would naturally
+    // pick. Since cross-class fallback can carve the block from a larger class instead, use
+    // `usable_size_of` with the actual pointer when the real backing size matters.
     pub fn usable_size(&self, layout: &Layout) -> (usize, usize) {
         match Heap::layout_to_allocator(&layout) {
-            HeapAllocator::Slab64Bytes => (layout.size(), 64),
-            HeapAllocator::Slab128Bytes => (layout.size(), 128),
-            HeapAllocator::Slab256Bytes => (layout.size(), 256),
-            HeapAllocator::Slab512Bytes => (layout.size(), 512),
-            HeapAllocator::Slab1024Bytes => (layout.size(), 1024),
-            HeapAllocator::Slab2048Bytes => (layout.size(), 2048),
-            HeapAllocator::Slab4096Bytes => (layout.size(), 4096),
-            HeapAllocator::LinkedListAllocator => (layout.size(), layout.size()),
+            HeapAllocator::Slab64Bytes => (layout.size(), 64 - FALLBACK_HEADER_SIZE),
+            HeapAllocator::Slab128Bytes => (layout.size(), 128 - FALLBACK_HEADER_SIZE),
+            HeapAllocator::Slab256Bytes => (layout.size(), 256 - FALLBACK_HEADER_SIZE),
+            HeapAllocator::Slab512Bytes => (layout.size(), 512 - FALLBACK_HEADER_SIZE),
+            HeapAllocator::Slab1024Bytes => (layout.size(), 1024 - FALLBACK_HEADER_SIZE),
+            HeapAllocator::Slab2048Bytes => (layout.size(), 2048 - FALLBACK_HEADER_SIZE),
+            HeapAllocator::Slab4096Bytes => (layout.size(), 4096 - FALLBACK_HEADER_SIZE),
+            HeapAllocator::BuddySystemAllocator => {
+                // `BuddySystemAllocator` is last in `CLASS_FALLBACK_ORDER`, so a request that
+                // prefers it is never itself a fallback target for a larger class — it carries
+                // no header and pays no `FALLBACK_HEADER_SIZE` tax.
+                self.buddy_allocator.usable_size(layout)
+            }
         }
     }
 
-    // Finds allocator to use based on layout size and alignment
+    // Returns the true usable size of a live allocation at `ptr`, accounting for cross-class
+    // fallback: `ptr` may be backed by a larger class than `layout` alone would suggest, because
+    // `allocate` reaches for the next non-empty class rather than failing. `ptr` must be a
+    // pointer currently returned by `allocate` with this same `layout`; undefined behavior may
+    // occur otherwise, thus this function is unsafe.
+    pub unsafe fn usable_size_of(&self, ptr: NonNull<u8>, layout: &Layout) -> usize {
+        let preferred = Heap::layout_to_allocator(layout);
+        let addr = ptr.as_ptr() as usize;
+
+        if self.class_contains(preferred, addr) {
+            // Served directly: no header, no offset, full class capacity.
+            return match preferred {
+                HeapAllocator::Slab64Bytes => 64,
+                HeapAllocator::Slab128Bytes => 128,
+                HeapAllocator::Slab256Bytes => 256,
+                HeapAllocator::Slab512Bytes => 512,
+                HeapAllocator::Slab1024Bytes => 1024,
+                HeapAllocator::Slab2048Bytes => 2048,
+                HeapAllocator::Slab4096Bytes => 4096,
+                HeapAllocator::BuddySystemAllocator => self.buddy_allocator.usable_size(layout).1,
+            };
+        }
+
+        let block_addr = addr - FALLBACK_HEADER_SIZE;
+        let owner = (block_addr as *const HeapAllocator).read();
+
+        let block_size = match owner {
+            HeapAllocator::Slab64Bytes => 64,
+            HeapAllocator::Slab128Bytes => 128,
+            HeapAllocator::Slab256Bytes => 256,
+            HeapAllocator::Slab512Bytes => 512,
+            HeapAllocator::Slab1024Bytes => 1024,
+            HeapAllocator::Slab2048Bytes => 2048,
+            HeapAllocator::Slab4096Bytes => 4096,
+            HeapAllocator::BuddySystemAllocator => {
+                let padded = Heap::padded_layout(layout).expect("layout overflowed on padding");
+                self.buddy_allocator.usable_size(&padded).1
+            }
+        };
+
+        block_size - FALLBACK_HEADER_SIZE
+    }
+
+    // Finds allocator to use based on layout size and alignment. Each class's ceiling sits
+    // `FALLBACK_HEADER_SIZE` below its raw block size, so a request that lands in a class here is
+    // guaranteed to still fit alongside the owner tag if that class is later reached as a
+    // fallback target for some smaller, exhausted class — the common, directly-served path never
+    // sees or pays for that header at all.
     pub fn layout_to_allocator(layout: &Layout) -> HeapAllocator {
-        if layout.size() > 4096 {
-            HeapAllocator::LinkedListAllocator
-        } else if layout.size() <= 64 && layout.align() <= 64 {
+        if layout.size() > 4096 - FALLBACK_HEADER_SIZE {
+            HeapAllocator::BuddySystemAllocator
+        } else if layout.size() <= 64 - FALLBACK_HEADER_SIZE
+            && layout.align() <= 64 - FALLBACK_HEADER_SIZE
+        {
             HeapAllocator::Slab64Bytes
-        } else if layout.size() <= 128 && layout.align() <= 128 {
+        } else if layout.size() <= 128 - FALLBACK_HEADER_SIZE
+            && layout.align() <= 128 - FALLBACK_HEADER_SIZE
+        {
             HeapAllocator::Slab128Bytes
-        } else if layout.size() <= 256 && layout.align() <= 256 {
+        } else if layout.size() <= 256 - FALLBACK_HEADER_SIZE
+            && layout.align() <= 256 - FALLBACK_HEADER_SIZE
+        {
             HeapAllocator::Slab256Bytes
-        } else if layout.size() <= 512 && layout.align() <= 512 {
+        } else if layout.size() <= 512 - FALLBACK_HEADER_SIZE
+            && layout.align() <= 512 - FALLBACK_HEADER_SIZE
+        {
             HeapAllocator::Slab512Bytes
-        } else if layout.size() <= 1024 && layout.align() <= 1024 {
+        } else if layout.size() <= 1024 - FALLBACK_HEADER_SIZE
+            && layout.align() <= 1024 - FALLBACK_HEADER_SIZE
+        {
             HeapAllocator::Slab1024Bytes
-        } else if layout.size() <= 2048 && layout.align() <= 2048 {
+        } else if layout.size() <= 2048 - FALLBACK_HEADER_SIZE
+            && layout.align() <= 2048 - FALLBACK_HEADER_SIZE
+        {
             HeapAllocator::Slab2048Bytes
         } else {
             HeapAllocator::Slab4096Bytes
         }
     }
+
+    // Snapshots per-class occupancy for every slab plus the buddy region, so callers can detect
+    // a class nearing exhaustion (and `grow` it) or log fragmentation, neither of which is
+    // possible from the outside when `allocate` simply returns `AllocErr` on a full class.
+    pub fn stats(&self) -> HeapStats {
+        let (buddy_total_bytes, buddy_free_bytes) = self.buddy_allocator.stats();
+        HeapStats {
+            slab_64_bytes: self.slab_64_bytes.stats(),
+            slab_128_bytes: self.slab_128_bytes.stats(),
+            slab_256_bytes: self.slab_256_bytes.stats(),
+            slab_512_bytes: self.slab_512_bytes.stats(),
+            slab_1024_bytes: self.slab_1024_bytes.stats(),
+            slab_2048_bytes: self.slab_2048_bytes.stats(),
+            slab_4096_bytes: self.slab_4096_bytes.stats(),
+            buddy_total_bytes,
+            buddy_free_bytes,
+            buddy_bytes_in_use: buddy_total_bytes - buddy_free_bytes,
+        }
+    }
+}
+
+// Aggregate occupancy snapshot for the whole heap, as returned by `Heap::stats` and
+// `LockedHeap::stats`.
+#[derive(Copy, Clone, Debug)]
+pub struct HeapStats {
+    pub slab_64_bytes: SlabStats,
+    pub slab_128_bytes: SlabStats,
+    pub slab_256_bytes: SlabStats,
+    pub slab_512_bytes: SlabStats,
+    pub slab_1024_bytes: SlabStats,
+    pub slab_2048_bytes: SlabStats,
+    pub slab_4096_bytes: SlabStats,
+    pub buddy_total_bytes: usize,
+    pub buddy_free_bytes: usize,
+    pub buddy_bytes_in_use: usize,
 }
 
 // these two structs are for testing only
@@ -211,6 +916,14 @@ impl LockedHeap {
     pub unsafe fn new(heap_start_addr: usize, heap_size: usize) -> LockedHeap {
         LockedHeap(Mutex::new(Some(Heap::new(heap_start_addr, heap_size))))
     }
+
+    pub fn stats(&self) -> HeapStats {
+        if let Some(ref heap) = *self.0.lock() {
+            heap.stats()
+        } else {
+            panic!("stats: heap not initialized");
+        }
+    }
 }
 
 impl Deref for LockedHeap {
@@ -278,10 +991,63 @@ macro_rules! init_heap {
         let heap_size = heap_end - heap_start;
         unsafe {
             ALLOCATOR.init(heap_start, heap_size);
-        }    
+        }
     }};
 }
 
+// Declares a `#[repr(align(4096))]` static byte array sized `LEN` (rounded up to a multiple of
+// `MIN_HEAP_SIZE`), a `LockedHeap` that manages it, and a safe-to-call-once `init()` that wires
+// the two together. This mirrors how embedded/VM no_std projects bootstrap their global
+// allocator from a static buffer, removing the need for callers to compute and validate a
+// `heap_start_addr`/`heap_size` pair by hand the way `init_heap!` requires.
+//
+// Expands to a module named `$name` exposing `$name::ALLOCATOR` (already wired up as the
+// crate's `#[global_allocator]` — no further attribute needed from the caller) and
+// `$name::init()`. Since `#[global_allocator]` may only be used once per crate, invoke this
+// macro at most once.
+//
+// The attribute is skipped under `cfg(test)`: a crate's own test binary links every test into
+// one process, and the moment `ALLOCATOR` is installed as *the* global allocator, any
+// allocation the test harness makes before that test's body calls `init()` panics and aborts
+// the whole binary. Downstream crates that depend on this one and invoke the macro from their
+// own (non-test) code are unaffected — `cfg(test)` only ever refers to the crate currently
+// being compiled.
+#[macro_export]
+macro_rules! configure_heap {
+    ($name:ident, $len:expr) => {
+        mod $name {
+            const HEAP_LEN: usize = (($len + $crate::MIN_HEAP_SIZE - 1)
+                / $crate::MIN_HEAP_SIZE)
+                * $crate::MIN_HEAP_SIZE;
+
+            #[repr(align(4096))]
+            struct HeapStorage([u8; HEAP_LEN]);
+
+            static mut HEAP_STORAGE: HeapStorage = HeapStorage([0; HEAP_LEN]);
+
+            #[cfg_attr(not(test), global_allocator)]
+            pub static ALLOCATOR: $crate::LockedHeap = $crate::LockedHeap::empty();
+
+            static INITIALIZED: core::sync::atomic::AtomicBool =
+                core::sync::atomic::AtomicBool::new(false);
+
+            // Initializes `ALLOCATOR` from the static buffer declared by this module. Safe to
+            // call once; panics if called again, since re-initializing a heap that already has
+            // live allocations would be undefined behavior.
+            pub fn init() {
+                assert!(
+                    !INITIALIZED.swap(true, core::sync::atomic::Ordering::SeqCst),
+                    "heap already initialized"
+                );
+                unsafe {
+                    let start = &HEAP_STORAGE.0 as *const u8 as usize;
+                    ALLOCATOR.init(start, HEAP_LEN);
+                }
+            }
+        }
+    };
+}
+
 // statistics
 pub fn mem_areas() {
     let boot_info = unsafe{ multiboot2::load(multiboot_information_address) };
@@ -295,10 +1061,6 @@ pub fn mem_areas() {
     }
 }
 
-pub fn memadvise() {
-    
-}
-
 #[test]
 pub fn new_heap() -> Heap {
     let test_heap = TestHeap {
@@ -348,3 +1110,241 @@ fn allocate_one_4096_block() {
         heap.deallocate(x, layout.clone());
     }
 }
+
+#[cfg(feature = "slab-bitmap")]
+#[test]
+#[should_panic(expected = "double free")]
+fn double_free_panics_with_slab_bitmap() {
+    let mut heap = new_heap();
+    let layout = Layout::from_size_align(8, 8).unwrap();
+
+    let ptr = heap.allocate(layout.clone()).unwrap();
+    unsafe {
+        heap.deallocate(ptr, layout.clone());
+        heap.deallocate(ptr, layout);
+    }
+}
+
+#[cfg(feature = "slab-bitmap")]
+#[test]
+fn advise_free_decommit_then_realloc_preserves_correctness() {
+    let mut heap = new_heap();
+    let layout = Layout::from_size_align(8, 8).unwrap();
+
+    // Fill the whole 64-byte slab's one page, then free every block so the page is eligible
+    // for decommit.
+    let blocks_per_page = MIN_SLAB_SIZE / 64;
+    let mut blocks = [None; 64];
+    for slot in blocks.iter_mut().take(blocks_per_page) {
+        *slot = Some(heap.allocate(layout.clone()).unwrap());
+    }
+    for slot in blocks.iter().take(blocks_per_page) {
+        unsafe {
+            heap.deallocate(slot.unwrap(), layout.clone());
+        }
+    }
+
+    heap.advise_free();
+
+    // Re-allocating and writing through the returned pointer must still work correctly once
+    // the page has been handed back to the OS and is re-faulted/zeroed on reuse.
+    let ptr = heap.allocate(layout.clone()).unwrap();
+    unsafe {
+        ptr.as_ptr().write(0x42);
+        assert_eq!(ptr.as_ptr().read(), 0x42);
+        heap.deallocate(ptr, layout);
+    }
+}
+
+configure_heap!(configured_test_heap, HEAP_SIZE);
+
+#[test]
+fn configure_heap_serves_allocations_from_its_static_buffer() {
+    let layout = Layout::from_size_align(size_of::<u64>(), align_of::<u64>()).unwrap();
+
+    unsafe {
+        configured_test_heap::init();
+
+        let ptr = configured_test_heap::ALLOCATOR.alloc(layout.clone());
+        assert!(!ptr.is_null());
+
+        (ptr as *mut u64).write(0xdead_beef_u64);
+        assert_eq!((ptr as *mut u64).read(), 0xdead_beef_u64);
+
+        configured_test_heap::ALLOCATOR.dealloc(ptr, layout);
+    }
+}
+
+#[test]
+fn stats_reflects_live_alloc_and_dealloc_counts() {
+    let mut heap = new_heap();
+    let layout = Layout::from_size_align(8, 8).unwrap();
+
+    let before = heap.stats().slab_64_bytes;
+    assert_eq!(before.free_blocks, before.total_blocks);
+    assert_eq!(before.bytes_in_use, 0);
+
+    let ptr = heap.allocate(layout.clone()).unwrap();
+
+    let after_alloc = heap.stats().slab_64_bytes;
+    assert_eq!(after_alloc.free_blocks, before.free_blocks - 1);
+    assert_eq!(after_alloc.bytes_in_use, after_alloc.block_size);
+
+    unsafe {
+        heap.deallocate(ptr, layout);
+    }
+
+    let after_dealloc = heap.stats().slab_64_bytes;
+    assert_eq!(after_dealloc.free_blocks, before.free_blocks);
+    assert_eq!(after_dealloc.bytes_in_use, 0);
+}
+
+#[test]
+fn idle_buddy_region_reports_zero_bytes_in_use() {
+    // `BIG_HEAP_SIZE = HEAP_SIZE * 10` gives the buddy region a non-power-of-two-multiple of
+    // `MIN_SLAB_SIZE` size, which used to leave its unrounded tail counted in `total_bytes` but
+    // never trackable as free, reporting phantom bytes in use on a completely idle heap.
+    let heap = new_big_heap();
+    let stats = heap.stats();
+    assert_eq!(stats.buddy_bytes_in_use, 0);
+    assert_eq!(stats.buddy_total_bytes, stats.buddy_free_bytes);
+}
+
+// Backing storage for the `BuddyAllocator`-only tests below, sized to 4 `MIN_SLAB_SIZE` blocks
+// so a split can be exercised without reaching into the rest of a full `Heap`.
+#[repr(align(4096))]
+struct TestBuddyRegion {
+    space: [u8; 4 * MIN_SLAB_SIZE],
+}
+
+fn new_buddy_allocator() -> (TestBuddyRegion, BuddyAllocator) {
+    let region = TestBuddyRegion {
+        space: [0; 4 * MIN_SLAB_SIZE],
+    };
+    let allocator =
+        unsafe { BuddyAllocator::new(&region.space[0] as *const u8 as usize, 4 * MIN_SLAB_SIZE) };
+    (region, allocator)
+}
+
+#[test]
+fn buddy_split_then_merge_round_trip() {
+    let (_region, mut allocator) = new_buddy_allocator();
+    let layout = Layout::from_size_align(MIN_SLAB_SIZE, MIN_SLAB_SIZE).unwrap();
+
+    // Splits the single order-2 free block down to order 0, handing out one order-0 block and
+    // pushing its order-0 and order-1 buddies onto their own free lists.
+    let a = unsafe { allocator.allocate(layout.clone()).unwrap() };
+    assert_eq!(allocator.stats().1, 3 * MIN_SLAB_SIZE);
+
+    unsafe {
+        allocator.deallocate(a, layout);
+    }
+
+    // Freeing the only live block must walk all the way back up to a single order-2 block,
+    // i.e. every byte of the region is free again.
+    let (total, free) = allocator.stats();
+    assert_eq!(free, total);
+}
+
+#[test]
+fn buddy_interleaved_allocations_split_and_merge_independently() {
+    let (_region, mut allocator) = new_buddy_allocator();
+    let layout = Layout::from_size_align(MIN_SLAB_SIZE, MIN_SLAB_SIZE).unwrap();
+
+    let a = unsafe { allocator.allocate(layout.clone()).unwrap() };
+    let b = unsafe { allocator.allocate(layout.clone()).unwrap() };
+    assert_ne!(a.as_ptr(), b.as_ptr());
+
+    // `a`'s buddy is still live, so freeing it alone must not merge into a block larger than
+    // order 0.
+    unsafe {
+        allocator.deallocate(a, layout.clone());
+    }
+    assert_eq!(allocator.stats().1, 3 * MIN_SLAB_SIZE);
+
+    let c = unsafe { allocator.allocate(layout.clone()).unwrap() };
+    assert_eq!(a.as_ptr(), c.as_ptr());
+
+    unsafe {
+        allocator.deallocate(b, layout.clone());
+        allocator.deallocate(c, layout);
+    }
+    let (total, free) = allocator.stats();
+    assert_eq!(free, total);
+}
+
+#[test]
+fn buddy_usable_size_rounds_up_to_order_size() {
+    let (_region, allocator) = new_buddy_allocator();
+
+    let small = Layout::from_size_align(1, 1).unwrap();
+    assert_eq!(allocator.usable_size(&small), (1, MIN_SLAB_SIZE));
+
+    let just_over_one_block = Layout::from_size_align(MIN_SLAB_SIZE + 1, 1).unwrap();
+    assert_eq!(
+        allocator.usable_size(&just_over_one_block),
+        (MIN_SLAB_SIZE + 1, 2 * MIN_SLAB_SIZE)
+    );
+}
+
+#[test]
+fn buddy_allocator_rounds_non_power_of_two_region_down() {
+    // 3 is not a power of two, so only the largest power-of-two-sized prefix (2 * MIN_SLAB_SIZE)
+    // should ever be tracked; the remaining MIN_SLAB_SIZE tail must not silently count toward
+    // total/free bytes.
+    let region = TestBuddyRegion {
+        space: [0; 4 * MIN_SLAB_SIZE],
+    };
+    let allocator =
+        unsafe { BuddyAllocator::new(&region.space[0] as *const u8 as usize, 3 * MIN_SLAB_SIZE) };
+
+    let (total, free) = allocator.stats();
+    assert_eq!(total, 2 * MIN_SLAB_SIZE);
+    assert_eq!(free, total);
+}
+
+#[test]
+fn exhausted_class_falls_back_to_a_larger_one() {
+    let mut heap = new_heap();
+    let layout = Layout::from_size_align(8, 8).unwrap();
+    let total_64_byte_blocks = heap.stats().slab_64_bytes.total_blocks;
+
+    let mut held = [None; 64];
+    for slot in held.iter_mut().take(total_64_byte_blocks) {
+        *slot = Some(heap.allocate(layout.clone()).unwrap());
+    }
+    assert_eq!(heap.stats().slab_64_bytes.free_blocks, 0);
+
+    let free_128_before_fallback = heap.stats().slab_128_bytes.free_blocks;
+
+    // The 64-byte slab is now exhausted, so this request must be carved from the 128-byte
+    // slab instead of failing outright.
+    let fallback_ptr = heap.allocate(layout.clone()).unwrap();
+    assert_eq!(heap.stats().slab_64_bytes.free_blocks, 0);
+    assert_eq!(
+        heap.stats().slab_128_bytes.free_blocks,
+        free_128_before_fallback - 1
+    );
+
+    // Writing the full requested size through the returned pointer must read back intact and
+    // leave the block's own owner tag in place, so the deallocate below still returns it to
+    // the 128-byte slab.
+    unsafe {
+        core::ptr::write_bytes(fallback_ptr.as_ptr(), 0xAB, layout.size());
+        for i in 0..layout.size() {
+            assert_eq!(*fallback_ptr.as_ptr().add(i), 0xAB);
+        }
+
+        heap.deallocate(fallback_ptr, layout.clone());
+    }
+    assert_eq!(
+        heap.stats().slab_128_bytes.free_blocks,
+        free_128_before_fallback
+    );
+
+    for slot in held.iter().take(total_64_byte_blocks) {
+        unsafe {
+            heap.deallocate(slot.unwrap(), layout.clone());
+        }
+    }
+}